@@ -0,0 +1,454 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Bundled food database, keyed by normalized name, shipped with the binary.
+const EMBEDDED_FOODS_JSON: &str = include_str!("../assets/foods.json");
+
+/// Whether a [`FoodEntry`]'s macros describe 100g of the food, or a single unit of it
+/// (e.g. "1 large egg").
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+enum Basis {
+    #[default]
+    #[serde(rename = "per_100g")]
+    Per100g,
+    #[serde(rename = "per_unit")]
+    PerUnit,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FoodEntry {
+    pub kcal: f32,
+    pub protein: f32,
+    pub carbs: f32,
+    pub fat: f32,
+    #[serde(default)]
+    basis: Basis,
+}
+
+pub type FoodDatabase = BTreeMap<String, FoodEntry>;
+
+/// Sum of macros resolved from an ingredient string.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Macros {
+    pub kcal: f32,
+    pub protein: f32,
+    pub carbs: f32,
+    pub fat: f32,
+}
+
+impl Macros {
+    fn add_scaled(&mut self, entry: &FoodEntry, scale: f32) {
+        self.kcal += entry.kcal * scale;
+        self.protein += entry.protein * scale;
+        self.carbs += entry.carbs * scale;
+        self.fat += entry.fat * scale;
+    }
+
+    pub fn add(&mut self, other: Macros) {
+        self.kcal += other.kcal;
+        self.protein += other.protein;
+        self.carbs += other.carbs;
+        self.fat += other.fat;
+    }
+
+    pub fn scale(self, factor: f32) -> Macros {
+        Macros {
+            kcal: self.kcal * factor,
+            protein: self.protein * factor,
+            carbs: self.carbs * factor,
+            fat: self.fat * factor,
+        }
+    }
+}
+
+fn food_db_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("food_db.json")
+}
+
+pub fn normalize(name: &str) -> String {
+    name.trim()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Loads the food database, seeding it from the embedded defaults on first run.
+pub fn load_food_db(data_dir: &Path) -> FoodDatabase {
+    let path = food_db_path(data_dir);
+
+    if let Ok(contents) = fs::read_to_string(&path) {
+        if let Ok(db) = serde_json::from_str(&contents) {
+            return db;
+        }
+    }
+
+    let db: FoodDatabase =
+        serde_json::from_str(EMBEDDED_FOODS_JSON).expect("embedded food database is valid JSON");
+    let _ = save_food_db(data_dir, &db);
+    db
+}
+
+pub fn save_food_db(data_dir: &Path, db: &FoodDatabase) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(db)?;
+    fs::write(food_db_path(data_dir), json)
+}
+
+/// Adds or overwrites an entry in the food database, keyed by its normalized name.
+pub fn add_food(db: &mut FoodDatabase, name: &str, kcal: f32, protein: f32, carbs: f32, fat: f32) {
+    db.insert(
+        normalize(name),
+        FoodEntry {
+            kcal,
+            protein,
+            carbs,
+            fat,
+            basis: Basis::Per100g,
+        },
+    );
+}
+
+/// Unit words this parser understands, each mapped to the number of grams (or
+/// grams-equivalent, for the water-like volume units) one unit is worth.
+fn unit_to_grams(unit: &str) -> Option<f32> {
+    Some(match unit {
+        "g" | "gram" | "grams" => 1.0,
+        "kg" | "kilogram" | "kilograms" => 1000.0,
+        "ml" | "milliliter" | "milliliters" | "millilitre" | "millilitres" => 1.0,
+        "l" | "liter" | "liters" | "litre" | "litres" => 1000.0,
+        "tsp" | "teaspoon" | "teaspoons" => 4.9,
+        "tbsp" | "tablespoon" | "tablespoons" => 14.8,
+        "cup" | "cups" => 240.0,
+        "oz" | "ounce" | "ounces" => 28.3495,
+        "lb" | "lbs" | "pound" | "pounds" => 453.592,
+        _ => return None,
+    })
+}
+
+const DESCRIPTORS: &[&str] = &[
+    "large", "medium", "small", "chopped", "diced", "fresh", "ripe", "raw", "cooked", "whole",
+];
+
+/// A single vulgar-fraction glyph, e.g. the "¾" in "4¾ cups flour".
+fn fraction_value(c: char) -> Option<f32> {
+    Some(match c {
+        '¼' => 0.25,
+        '½' => 0.5,
+        '¾' => 0.75,
+        '⅓' => 1.0 / 3.0,
+        '⅔' => 2.0 / 3.0,
+        '⅛' => 0.125,
+        '⅜' => 0.375,
+        '⅝' => 0.625,
+        '⅞' => 0.875,
+        _ => return None,
+    })
+}
+
+/// Pulls a leading quantity (decimal, "3/4" fraction, or "4¾" mixed number) off the
+/// front of `s`, returning the quantity and the unconsumed remainder.
+fn parse_quantity(s: &str) -> (f32, &str) {
+    let bytes_consumed = s
+        .char_indices()
+        .take_while(|(_, c)| {
+            c.is_ascii_digit() || *c == '.' || *c == '/' || fraction_value(*c).is_some()
+        })
+        .last()
+        .map(|(i, c)| i + c.len_utf8());
+
+    let Some(end) = bytes_consumed else {
+        return (1.0, s);
+    };
+
+    let (token, rest) = s.split_at(end);
+
+    if let Some(last) = token.chars().last() {
+        if let Some(frac) = fraction_value(last) {
+            let whole: f32 = token[..token.len() - last.len_utf8()]
+                .parse()
+                .unwrap_or(0.0);
+            return (whole + frac, rest);
+        }
+    }
+
+    if let Some((num, den)) = token.split_once('/') {
+        if let (Ok(num), Ok(den)) = (num.parse::<f32>(), den.parse::<f32>()) {
+            if den != 0.0 {
+                return (num / den, rest);
+            }
+        }
+    }
+
+    match token.parse::<f32>() {
+        Ok(n) => (n, rest),
+        Err(_) => (1.0, s),
+    }
+}
+
+/// A single ingredient parsed out of a `cali log food` string, or stored as part of
+/// a recipe, before database lookup.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct IngredientAmount {
+    pub quantity: f32,
+    pub unit: Option<String>,
+    pub name: String,
+}
+
+/// Parses one ingredient segment, e.g. "135g plain flour" or "1 large egg".
+pub fn parse_ingredient_line(segment: &str) -> Option<IngredientAmount> {
+    let segment = segment.trim();
+    if segment.is_empty() {
+        return None;
+    }
+
+    let (quantity, rest) = parse_quantity(segment);
+    let rest = rest.trim_start();
+
+    let unit_len = rest
+        .char_indices()
+        .take_while(|(_, c)| c.is_alphabetic())
+        .last()
+        .map(|(i, c)| i + c.len_utf8())
+        .unwrap_or(0);
+    let (candidate, after_unit) = rest.split_at(unit_len);
+    let (unit, name_start) = match unit_to_grams(&candidate.to_lowercase()) {
+        Some(_) => (Some(candidate.to_lowercase()), after_unit),
+        None => (None, rest),
+    };
+
+    let name = name_start
+        .split_whitespace()
+        .filter(|word| !DESCRIPTORS.contains(&word.to_lowercase().as_str()))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let name = normalize(&name);
+
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(IngredientAmount {
+        quantity,
+        unit,
+        name,
+    })
+}
+
+/// Whether two single words refer to the same thing, allowing for a trailing
+/// plural "s" ("egg" / "eggs") but nothing looser than that.
+fn words_match(a: &str, b: &str) -> bool {
+    a == b || format!("{a}s") == b || format!("{b}s") == a
+}
+
+/// Whether `needle`'s words appear as a contiguous run of whole words somewhere in
+/// `haystack`'s words, e.g. "flour" inside "plain flour" and "eggs" inside "egg",
+/// but not "egg" inside "eggplant".
+fn contains_whole_words(haystack: &str, needle: &str) -> bool {
+    let haystack_words: Vec<&str> = haystack.split_whitespace().collect();
+    let needle_words: Vec<&str> = needle.split_whitespace().collect();
+
+    if needle_words.is_empty() || needle_words.len() > haystack_words.len() {
+        return false;
+    }
+
+    haystack_words.windows(needle_words.len()).any(|window| {
+        window
+            .iter()
+            .zip(needle_words.iter())
+            .all(|(w, n)| words_match(w, n))
+    })
+}
+
+/// Looks up `name` in the database: exact match first, then the most specific
+/// (longest) whole-word match in either direction, so "egg" doesn't match
+/// "eggplant" and "flour" still matches "plain flour".
+fn lookup<'a>(db: &'a FoodDatabase, name: &str) -> Option<&'a FoodEntry> {
+    if let Some(entry) = db.get(name) {
+        return Some(entry);
+    }
+
+    db.iter()
+        .filter(|(key, _)| contains_whole_words(name, key) || contains_whole_words(key, name))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, entry)| entry)
+}
+
+/// Resolves a single already-parsed ingredient against the food database.
+pub fn resolve_ingredient(ingredient: &IngredientAmount, db: &FoodDatabase) -> Option<Macros> {
+    let entry = lookup(db, &ingredient.name)?;
+
+    let scale = match entry.basis {
+        // A weight/volume unit doesn't make sense for a food whose macros are given
+        // per-unit (e.g. "50g egg" isn't 50 eggs) - treat it as unresolved rather
+        // than silently misreading the quantity as a unit count.
+        Basis::PerUnit if ingredient.unit.is_some() => return None,
+        Basis::PerUnit => ingredient.quantity,
+        Basis::Per100g => {
+            let grams = match &ingredient.unit {
+                Some(unit) => ingredient.quantity * unit_to_grams(unit).unwrap_or(1.0),
+                None => ingredient.quantity,
+            };
+            grams / 100.0
+        }
+    };
+
+    let mut macros = Macros::default();
+    macros.add_scaled(entry, scale);
+    Some(macros)
+}
+
+/// Resolves a free-form, comma-separated ingredient string against the food database,
+/// returning the summed macros and the names of any ingredients that didn't match.
+pub fn resolve_ingredients(input: &str, db: &FoodDatabase) -> (Macros, Vec<String>) {
+    let mut totals = Macros::default();
+    let mut unmatched = Vec::new();
+
+    for segment in input.split(',') {
+        let Some(ingredient) = parse_ingredient_line(segment) else {
+            continue;
+        };
+
+        match resolve_ingredient(&ingredient, db) {
+            Some(macros) => totals.add(macros),
+            None => unmatched.push(ingredient.name),
+        }
+    }
+
+    (totals, unmatched)
+}
+
+/// Like [`resolve_ingredients`], but any ingredient that doesn't resolve against `db`
+/// (including a per-unit food given with a weight or volume unit) is looked up
+/// with `lookup` and, if found, inserted into `db` (keyed by its normalized name) so
+/// it resolves immediately and is cached for next time. Returns the summed macros,
+/// the names of ingredients that couldn't be resolved even online, and whether `db`
+/// was modified (so the caller knows to persist it).
+pub fn resolve_ingredients_with_online(
+    input: &str,
+    db: &mut FoodDatabase,
+    mut lookup: impl FnMut(&str) -> Option<FoodEntry>,
+) -> (Macros, Vec<String>, bool) {
+    let mut totals = Macros::default();
+    let mut unmatched = Vec::new();
+    let mut db_changed = false;
+
+    for segment in input.split(',') {
+        let Some(ingredient) = parse_ingredient_line(segment) else {
+            continue;
+        };
+
+        if resolve_ingredient(&ingredient, db).is_none() {
+            if let Some(entry) = lookup(&ingredient.name) {
+                db.insert(ingredient.name.clone(), entry);
+                db_changed = true;
+            }
+        }
+
+        match resolve_ingredient(&ingredient, db) {
+            Some(macros) => totals.add(macros),
+            None => unmatched.push(ingredient.name),
+        }
+    }
+
+    (totals, unmatched, db_changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_db() -> FoodDatabase {
+        let mut db = FoodDatabase::new();
+        db.insert(
+            "egg".to_string(),
+            FoodEntry {
+                kcal: 72.0,
+                protein: 6.3,
+                carbs: 0.4,
+                fat: 4.8,
+                basis: Basis::PerUnit,
+            },
+        );
+        db.insert(
+            "eggplant".to_string(),
+            FoodEntry {
+                kcal: 25.0,
+                protein: 1.0,
+                carbs: 6.0,
+                fat: 0.2,
+                basis: Basis::Per100g,
+            },
+        );
+        db.insert(
+            "plain flour".to_string(),
+            FoodEntry {
+                kcal: 364.0,
+                protein: 10.0,
+                carbs: 76.0,
+                fat: 1.0,
+                basis: Basis::Per100g,
+            },
+        );
+        db
+    }
+
+    #[test]
+    fn parse_ingredient_line_handles_decimal_unit_and_descriptors() {
+        let ingredient = parse_ingredient_line("135g plain flour").unwrap();
+        assert_eq!(ingredient.quantity, 135.0);
+        assert_eq!(ingredient.unit.as_deref(), Some("g"));
+        assert_eq!(ingredient.name, "plain flour");
+
+        let ingredient = parse_ingredient_line("1 large egg").unwrap();
+        assert_eq!(ingredient.quantity, 1.0);
+        assert_eq!(ingredient.unit, None);
+        assert_eq!(ingredient.name, "egg");
+    }
+
+    #[test]
+    fn parse_ingredient_line_handles_fractions() {
+        let ingredient = parse_ingredient_line("3/4 cup milk").unwrap();
+        assert!((ingredient.quantity - 0.75).abs() < 1e-6);
+
+        let ingredient = parse_ingredient_line("4¾ cups flour").unwrap();
+        assert!((ingredient.quantity - 4.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn eggplant_does_not_match_egg() {
+        let db = test_db();
+        let ingredient = parse_ingredient_line("100g eggplant").unwrap();
+        let macros = resolve_ingredient(&ingredient, &db).unwrap();
+
+        // 100g of eggplant (25 kcal/100g), not 100 "units" of egg.
+        assert!((macros.kcal - 25.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn substring_match_still_works_for_multi_word_keys() {
+        let db = test_db();
+        let ingredient = parse_ingredient_line("50g flour").unwrap();
+        let macros = resolve_ingredient(&ingredient, &db).unwrap();
+
+        assert!((macros.kcal - 182.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn per_unit_food_rejects_weight_units() {
+        let db = test_db();
+        let ingredient = parse_ingredient_line("50g egg").unwrap();
+
+        assert!(resolve_ingredient(&ingredient, &db).is_none());
+    }
+
+    #[test]
+    fn per_unit_food_scales_by_count() {
+        let db = test_db();
+        let ingredient = parse_ingredient_line("2 eggs").unwrap();
+        let macros = resolve_ingredient(&ingredient, &db).unwrap();
+
+        assert!((macros.kcal - 144.0).abs() < 1e-3);
+    }
+}
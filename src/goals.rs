@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Daily nutrition targets, loaded from `config.toml` in the user's config directory.
+/// Any field left unset has no goal and is skipped when rendering progress.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct Goals {
+    pub calories: Option<f32>,
+    pub water: Option<f32>,
+    pub protein: Option<f32>,
+    pub carbs: Option<f32>,
+    pub fat: Option<f32>,
+}
+
+fn config_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("cali");
+    std::fs::create_dir_all(&path).expect("Failed to create config directory");
+    path.push("config.toml");
+    path
+}
+
+pub fn load_goals() -> Goals {
+    let path = config_path();
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => toml::from_str(&contents).unwrap_or_default(),
+        Err(_) => Goals::default(),
+    }
+}
+
+pub fn save_goals(goals: &Goals) -> std::io::Result<()> {
+    let toml = toml::to_string_pretty(goals).expect("Goals always serializes to TOML");
+    fs::write(config_path(), toml)
+}
+
+/// Renders a `[████░░░░]` progress bar plus the (unrounded) percentage, clamping the
+/// filled portion at 100% even when the actual value overshoots the target.
+pub fn render_bar(actual: f32, target: f32, width: usize) -> (String, f32) {
+    let pct = if target > 0.0 {
+        (actual / target) * 100.0
+    } else {
+        0.0
+    };
+    let filled = ((pct.clamp(0.0, 100.0) / 100.0) * width as f32).round() as usize;
+    let bar = format!(
+        "[{}{}]",
+        "█".repeat(filled),
+        "░".repeat(width.saturating_sub(filled))
+    );
+    (bar, pct)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_bar_fills_proportionally_to_the_target() {
+        let (bar, pct) = render_bar(50.0, 100.0, 10);
+
+        assert_eq!(pct, 50.0);
+        assert_eq!(bar, "[█████░░░░░]");
+    }
+
+    #[test]
+    fn render_bar_clamps_the_fill_when_overshooting_the_target() {
+        let (bar, pct) = render_bar(150.0, 100.0, 10);
+
+        assert_eq!(pct, 150.0);
+        assert_eq!(bar, "[██████████]");
+    }
+
+    #[test]
+    fn render_bar_is_empty_for_a_zero_target() {
+        let (bar, pct) = render_bar(50.0, 0.0, 10);
+
+        assert_eq!(pct, 0.0);
+        assert_eq!(bar, "[░░░░░░░░░░]");
+    }
+}
@@ -1,10 +1,17 @@
-use chrono::Local;
-use clap::{CommandFactory, Parser, Subcommand};
+mod food;
+mod goals;
+mod recipes;
+mod remote;
+mod storage;
+
+use chrono::{Duration, Local};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::*;
+use goals::Goals;
 use serde::{Deserialize, Serialize};
-use std::fs::{File, OpenOptions};
-use std::io::{self, Read, Write};
+use std::io;
 use std::path::PathBuf;
+use storage::Storage;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
@@ -13,6 +20,11 @@ struct Cli {
     /// Optional calories to log (shorthand for 'log calories VALUE')
     calories: Option<f32>,
 
+    /// Directory to store cali's data file in (overrides CALI_DATA_DIR and the
+    /// platform default)
+    #[arg(long, global = true)]
+    data_dir: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -31,6 +43,10 @@ enum Commands {
         /// Date to show summary for (format: YYYY-MM-DD), defaults to today
         #[arg(short, long)]
         date: Option<String>,
+
+        /// List each individual logged entry for the day
+        #[arg(short, long)]
+        list: bool,
     },
 
     /// Show all recorded nutrition data
@@ -38,6 +54,187 @@ enum Commands {
 
     /// Reset today's nutrition data
     Reset,
+
+    /// Remove the most recently logged entry
+    Undo,
+
+    /// Manage the local food database
+    Food {
+        #[command(subcommand)]
+        action: FoodCommands,
+    },
+
+    /// Manage daily nutrition goals
+    Goals {
+        #[command(subcommand)]
+        action: GoalsCommands,
+    },
+
+    /// Manage saved recipes
+    Recipe {
+        #[command(subcommand)]
+        action: RecipeCommands,
+    },
+
+    /// Manage the cached results of online nutrition lookups
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
+    },
+
+    /// Show a metric's history over a date range as a terminal chart
+    Trend {
+        /// Number of days to look back, ending today
+        #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+        /// Which metric to chart
+        #[arg(long, value_enum, default_value_t = TrendMetric::Calories)]
+        metric: TrendMetric,
+        /// Chart every metric, stacked, instead of just one
+        #[arg(long)]
+        all: bool,
+        /// Dump the series in this format instead of rendering a chart
+        #[arg(long, value_enum)]
+        export: Option<ExportFormat>,
+    },
+}
+
+/// A nutrition metric that can be charted with `cali trend`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum TrendMetric {
+    Calories,
+    Water,
+    Protein,
+    Carbs,
+    Fat,
+}
+
+impl TrendMetric {
+    fn all() -> &'static [TrendMetric] {
+        &[
+            TrendMetric::Calories,
+            TrendMetric::Water,
+            TrendMetric::Protein,
+            TrendMetric::Carbs,
+            TrendMetric::Fat,
+        ]
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            TrendMetric::Calories => "Calories",
+            TrendMetric::Water => "Water",
+            TrendMetric::Protein => "Protein",
+            TrendMetric::Carbs => "Carbs",
+            TrendMetric::Fat => "Fat",
+        }
+    }
+
+    fn unit(self) -> &'static str {
+        match self {
+            TrendMetric::Calories => "",
+            TrendMetric::Water => " fl oz",
+            TrendMetric::Protein | TrendMetric::Carbs | TrendMetric::Fat => "g",
+        }
+    }
+
+    fn value(self, log: &DailyLog) -> f32 {
+        match self {
+            TrendMetric::Calories => log.calories(),
+            TrendMetric::Water => log.water(),
+            TrendMetric::Protein => log.protein(),
+            TrendMetric::Carbs => log.carbs(),
+            TrendMetric::Fat => log.fat(),
+        }
+    }
+
+    fn goal(self, goals: &Goals) -> Option<f32> {
+        match self {
+            TrendMetric::Calories => goals.calories,
+            TrendMetric::Water => goals.water,
+            TrendMetric::Protein => goals.protein,
+            TrendMetric::Carbs => goals.carbs,
+            TrendMetric::Fat => goals.fat,
+        }
+    }
+}
+
+/// Output format for `cali trend --export`.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum CacheCommands {
+    /// Delete the cached results of past online nutrition lookups
+    Clear,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum RecipeCommands {
+    /// Save a recipe made up of one or more ingredient lines
+    Add {
+        /// Name of the recipe, e.g. "oatmeal bowl"
+        name: String,
+        /// An ingredient line, e.g. "50g oats" (repeat this flag for each ingredient)
+        #[arg(long = "ingredient")]
+        ingredients: Vec<String>,
+        /// Number of servings the ingredient list yields
+        #[arg(long, default_value_t = 1.0)]
+        servings_yield: f32,
+    },
+    /// List saved recipes
+    List,
+    /// Show a recipe's ingredients
+    Show {
+        /// Name of the recipe to show
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum GoalsCommands {
+    /// Set one or more daily nutrition targets
+    Set {
+        /// Daily calorie target
+        #[arg(long)]
+        calories: Option<f32>,
+        /// Daily water target in fluid ounces (fl oz)
+        #[arg(long)]
+        water: Option<f32>,
+        /// Daily protein target in grams
+        #[arg(long)]
+        protein: Option<f32>,
+        /// Daily carbohydrates target in grams
+        #[arg(long)]
+        carbs: Option<f32>,
+        /// Daily fat target in grams
+        #[arg(long)]
+        fat: Option<f32>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum FoodCommands {
+    /// Add or update a food in the database, keyed by name
+    Add {
+        /// Name of the food, e.g. "chicken breast"
+        name: String,
+        /// Calories per 100g
+        #[arg(long)]
+        kcal: f32,
+        /// Protein in grams per 100g
+        #[arg(long)]
+        protein: f32,
+        /// Carbohydrates in grams per 100g
+        #[arg(long)]
+        carbs: f32,
+        /// Fat in grams per 100g
+        #[arg(long)]
+        fat: f32,
+    },
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -46,87 +243,181 @@ enum NutritionType {
     Calories {
         /// Amount of calories
         amount: f32,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
     },
     /// Log water intake in fluid ounces (fl oz)
     Water {
         /// Amount of water in fluid ounces (fl oz)
         fl_oz: f32,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
     },
     /// Log protein intake in grams
     Protein {
         /// Amount of protein in grams
         grams: f32,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
     },
     /// Log carbohydrates intake in grams
     Carbs {
         /// Amount of carbohydrates in grams
         grams: f32,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
     },
     /// Log fat intake in grams
     Fat {
         /// Amount of fat in grams
         grams: f32,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
     },
+    /// Log a meal from a free-form, comma-separated ingredient string
+    Food {
+        /// e.g. "135g plain flour, 1 tsp baking powder, 130ml milk, 1 large egg"
+        ingredients: String,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
+        /// Look up ingredients not found locally via the configured nutrition API
+        /// (see `CALI_NUTRITION_API_URL`), caching results for 30 days
+        #[arg(long)]
+        online: bool,
+    },
+    /// Log a saved recipe
+    Recipe {
+        /// Name of the recipe to log, e.g. "oatmeal bowl"
+        name: String,
+        /// Number of servings eaten
+        #[arg(long, default_value_t = 1.0)]
+        servings: f32,
+        /// Backdate the entry to this time of day (format: HH:MM), defaults to now
+        #[arg(long)]
+        at: Option<String>,
+    },
+}
+
+/// The kind of nutrition value a single [`Entry`] records.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum NutritionKind {
+    Calories,
+    Water,
+    Protein,
+    Carbs,
+    Fat,
+}
+
+/// A single logged entry within a day: what was logged, how much, at what time, and
+/// (optionally) where it came from, e.g. the ingredient string or recipe name.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Entry {
+    time: String,
+    kind: NutritionKind,
+    amount: f32,
+    source: Option<String>,
+    /// Identifies the single logging action that produced this entry: all entries
+    /// pushed together by one `log food`/`log recipe` call share a `group` (their
+    /// starting index in `entries`), so `undo` can remove exactly that action's
+    /// entries instead of re-deriving group membership from `time`/`source` equality,
+    /// which two separate actions can share.
+    group: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 struct DailyLog {
     date: String,
-    calories: f32,
-    water: f32,
-    protein: f32,
-    carbs: f32,
-    fat: f32,
+    entries: Vec<Entry>,
 }
 
 impl DailyLog {
     fn new(date: String) -> Self {
         DailyLog {
             date,
-            calories: 0.0,
-            water: 0.0,
-            protein: 0.0,
-            carbs: 0.0,
-            fat: 0.0,
+            entries: Vec::new(),
         }
     }
-}
 
-fn get_data_file_path() -> PathBuf {
-    // Store the data file in the project directory
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    path.push("data");
-    std::fs::create_dir_all(&path).expect("Failed to create data directory");
-    path.push("cali_data.json");
-    path
-}
+    /// Pushes a single entry as its own one-entry logging action (used by the plain
+    /// `log calories`/`log water`/etc. commands, which each log exactly one metric).
+    fn push_entry(
+        &mut self,
+        kind: NutritionKind,
+        amount: f32,
+        time: String,
+        source: Option<String>,
+    ) {
+        let group = self.entries.len();
+        self.entries.push(Entry {
+            time,
+            kind,
+            amount,
+            source,
+            group,
+        });
+    }
 
-fn load_logs() -> io::Result<Vec<DailyLog>> {
-    let path = get_data_file_path();
+    /// The group id a multi-entry logging action (e.g. `log food`/`log recipe`)
+    /// should tag all of its entries with, so `undo` can remove them as a unit.
+    fn next_group(&self) -> usize {
+        self.entries.len()
+    }
 
-    if !path.exists() {
-        return Ok(Vec::new());
+    fn total(&self, kind: NutritionKind) -> f32 {
+        // Avoid printing "-0.0" when there are no matching entries.
+        self.entries
+            .iter()
+            .filter(|entry| entry.kind == kind)
+            .map(|entry| entry.amount)
+            .sum::<f32>()
+            + 0.0
     }
 
-    let mut file = File::open(path)?;
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;
+    fn calories(&self) -> f32 {
+        self.total(NutritionKind::Calories)
+    }
 
-    let logs: Vec<DailyLog> = serde_json::from_str(&contents).unwrap_or_else(|_| Vec::new());
-    Ok(logs)
-}
+    fn water(&self) -> f32 {
+        self.total(NutritionKind::Water)
+    }
 
-fn save_logs(logs: &[DailyLog]) -> io::Result<()> {
-    let path = get_data_file_path();
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .truncate(true)
-        .open(path)?;
+    fn protein(&self) -> f32 {
+        self.total(NutritionKind::Protein)
+    }
 
-    let json = serde_json::to_string_pretty(logs)?;
-    file.write_all(json.as_bytes())?;
-    Ok(())
+    fn carbs(&self) -> f32 {
+        self.total(NutritionKind::Carbs)
+    }
+
+    fn fat(&self) -> f32 {
+        self.total(NutritionKind::Fat)
+    }
+}
+
+/// Parses a `--at HH:MM` flag into a `HH:MM` timestamp, falling back to the current
+/// time (and warning) if it doesn't parse, or if no time was given at all.
+fn resolve_time(at: Option<String>) -> String {
+    match at {
+        Some(raw) => match chrono::NaiveTime::parse_from_str(&raw, "%H:%M") {
+            Ok(time) => time.format("%H:%M").to_string(),
+            Err(_) => {
+                println!(
+                    "{} couldn't parse \"--at {}\" (expected HH:MM), using the current time instead",
+                    "Warning:".yellow().bold(),
+                    raw
+                );
+                Local::now().format("%H:%M").to_string()
+            }
+        },
+        None => Local::now().format("%H:%M").to_string(),
+    }
 }
 
 fn get_or_create_today_log(logs: &mut Vec<DailyLog>) -> &mut DailyLog {
@@ -140,69 +431,212 @@ fn get_or_create_today_log(logs: &mut Vec<DailyLog>) -> &mut DailyLog {
     }
 }
 
-fn log_nutrition(logs: &mut Vec<DailyLog>, nutrition_type: NutritionType) {
+fn log_nutrition(
+    logs: &mut Vec<DailyLog>,
+    nutrition_type: NutritionType,
+    data_dir: &std::path::Path,
+) {
     let today_log = get_or_create_today_log(logs);
 
     match nutrition_type {
-        NutritionType::Calories { amount } => {
-            today_log.calories += amount;
+        NutritionType::Calories { amount, at } => {
+            today_log.push_entry(NutritionKind::Calories, amount, resolve_time(at), None);
             println!(
                 "{} {} {}. {} {}",
                 "Logged".green(),
                 amount.to_string().green().bold(),
                 "calories".green(),
                 "Total today:".green(),
-                today_log.calories.to_string().green().bold(),
+                today_log.calories().to_string().green().bold(),
             );
         }
-        NutritionType::Water { fl_oz } => {
-            today_log.water += fl_oz;
+        NutritionType::Water { fl_oz, at } => {
+            today_log.push_entry(NutritionKind::Water, fl_oz, resolve_time(at), None);
             println!(
                 "{} {} {}. {} {}",
                 "Logged".blue(),
                 fl_oz.to_string().blue().bold(),
                 "fl oz of water".blue(),
                 "Total today:".blue(),
-                today_log.water.to_string().blue().bold()
+                today_log.water().to_string().blue().bold()
             );
         }
-        NutritionType::Protein { grams } => {
-            today_log.protein += grams;
+        NutritionType::Protein { grams, at } => {
+            today_log.push_entry(NutritionKind::Protein, grams, resolve_time(at), None);
             println!(
                 "{} {} {}. {} {}",
                 "Logged".yellow(),
                 grams.to_string().yellow().bold(),
                 "grams of protein".yellow(),
                 "Total today:".yellow(),
-                today_log.protein.to_string().yellow().bold()
+                today_log.protein().to_string().yellow().bold()
             );
         }
-        NutritionType::Carbs { grams } => {
-            today_log.carbs += grams;
+        NutritionType::Carbs { grams, at } => {
+            today_log.push_entry(NutritionKind::Carbs, grams, resolve_time(at), None);
             println!(
                 "{} {} {}. {} {}",
                 "Logged".purple(),
                 grams.to_string().purple().bold(),
                 "grams of carbs".purple(),
                 "Total today:".purple(),
-                today_log.carbs.to_string().purple().bold()
+                today_log.carbs().to_string().purple().bold()
             );
         }
-        NutritionType::Fat { grams } => {
-            today_log.fat += grams;
+        NutritionType::Fat { grams, at } => {
+            today_log.push_entry(NutritionKind::Fat, grams, resolve_time(at), None);
             println!(
                 "{} {} {}. {} {}",
                 "Logged".red(),
                 grams.to_string().red().bold(),
                 "grams of fat".red(),
                 "Total today:".red(),
-                today_log.fat.to_string().red().bold()
+                today_log.fat().to_string().red().bold()
+            );
+        }
+        NutritionType::Food {
+            ingredients,
+            at,
+            online,
+        } => {
+            let mut db = food::load_food_db(data_dir);
+
+            let (macros, unmatched) = if online {
+                let (macros, unmatched, db_changed) =
+                    food::resolve_ingredients_with_online(&ingredients, &mut db, |name| {
+                        remote::lookup_online(data_dir, name, Duration::days(30))
+                    });
+                if db_changed {
+                    let _ = food::save_food_db(data_dir, &db);
+                }
+                (macros, unmatched)
+            } else {
+                food::resolve_ingredients(&ingredients, &db)
+            };
+
+            log_macros(
+                today_log,
+                macros,
+                &unmatched,
+                resolve_time(at),
+                Some(ingredients),
             );
         }
+        NutritionType::Recipe { name, servings, at } => {
+            let db = food::load_food_db(data_dir);
+            let recipes = recipes::load_recipes(data_dir);
+
+            match recipes.get(&food::normalize(&name)) {
+                Some(recipe) => {
+                    let (macros, unmatched) = recipes::resolve_recipe(recipe, servings, &db);
+                    log_macros(today_log, macros, &unmatched, resolve_time(at), Some(name));
+                }
+                None => println!(
+                    "{} no recipe named \"{}\" found. Add one with `cali recipe add`.",
+                    "Warning:".yellow().bold(),
+                    name
+                ),
+            }
+        }
     }
 }
 
-fn reset_today_log(logs: &mut Vec<DailyLog>) -> io::Result<()> {
+/// Pushes the four macro entries (calories, protein, carbs, fat) a food or recipe
+/// log resolves to, and prints the resulting summary and any unmatched warnings.
+fn log_macros(
+    today_log: &mut DailyLog,
+    macros: food::Macros,
+    unmatched: &[String],
+    time: String,
+    source: Option<String>,
+) {
+    let group = today_log.next_group();
+    today_log.entries.push(Entry {
+        time: time.clone(),
+        kind: NutritionKind::Calories,
+        amount: macros.kcal,
+        source: source.clone(),
+        group,
+    });
+    today_log.entries.push(Entry {
+        time: time.clone(),
+        kind: NutritionKind::Protein,
+        amount: macros.protein,
+        source: source.clone(),
+        group,
+    });
+    today_log.entries.push(Entry {
+        time: time.clone(),
+        kind: NutritionKind::Carbs,
+        amount: macros.carbs,
+        source: source.clone(),
+        group,
+    });
+    today_log.entries.push(Entry {
+        time,
+        kind: NutritionKind::Fat,
+        amount: macros.fat,
+        source,
+        group,
+    });
+
+    println!(
+        "{} {}, {}, {}, {}",
+        "Logged:".green().bold(),
+        format!("{:.0} kcal", macros.kcal).green(),
+        format!("{:.1}g protein", macros.protein).yellow(),
+        format!("{:.1}g carbs", macros.carbs).purple(),
+        format!("{:.1}g fat", macros.fat).red(),
+    );
+
+    for name in unmatched {
+        println!(
+            "{} couldn't find \"{}\" in the food database, skipped it",
+            "Warning:".yellow().bold(),
+            name
+        );
+    }
+}
+
+/// Removes the most recently logged entry from today's log, if any. A food or recipe
+/// log pushes several entries (calories, protein, carbs, fat) that all share one
+/// `group`, so those are removed together rather than leaving a partial entry behind.
+fn undo_last_entry(logs: &mut [DailyLog]) {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+
+    let Some(log) = logs.iter_mut().find(|log| log.date == today) else {
+        println!("{}", "No entries today to undo.".bold());
+        return;
+    };
+
+    let Some(last) = log.entries.last() else {
+        println!("{}", "No entries today to undo.".bold());
+        return;
+    };
+
+    // All entries pushed by one logging action share a `group`, so cutting at the
+    // first entry whose group differs from the last entry's removes exactly that
+    // action's entries, whether it pushed one entry or several.
+    let group = last.group;
+    let cut = log
+        .entries
+        .iter()
+        .rposition(|entry| entry.group != group)
+        .map_or(0, |pos| pos + 1);
+
+    for entry in log.entries.split_off(cut) {
+        println!(
+            "{} {:?} {} {} {}",
+            "Undid".bold(),
+            entry.kind,
+            entry.amount,
+            "logged at".dimmed(),
+            entry.time
+        );
+    }
+}
+
+fn reset_today_log(logs: &mut [DailyLog]) {
     let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
 
     if let Some(index) = logs.iter().position(|log| log.date == today) {
@@ -211,11 +645,43 @@ fn reset_today_log(logs: &mut Vec<DailyLog>) -> io::Result<()> {
     } else {
         println!("{}", "No data for today to reset.".bold());
     }
+}
 
-    save_logs(logs)
+/// Prints one summary line for a metric. `actual_text` is already formatted with the
+/// metric's own precision and unit (e.g. "120.0g"); the target is printed as-is,
+/// followed by `unit`. Appends a
+/// progress bar against `goal`, and flags when the day's total has gone over it.
+fn print_metric_progress(
+    label_text: &str,
+    actual: f32,
+    actual_text: &str,
+    unit: &str,
+    goal: Option<f32>,
+) {
+    match goal {
+        Some(target) => {
+            let (bar, pct) = goals::render_bar(actual, target, 10);
+            let flag = if pct > 100.0 {
+                " over goal".red().bold().to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "{}: {} / {}{} {} {}{}",
+                label_text,
+                actual_text,
+                target,
+                unit,
+                bar.dimmed(),
+                format!("{:.0}%", pct).bold(),
+                flag
+            );
+        }
+        None => println!("{}: {}", label_text, actual_text),
+    }
 }
 
-fn show_summary(logs: &[DailyLog], date_str: Option<String>) {
+fn show_summary(logs: &[DailyLog], date_str: Option<String>, list: bool, goals: &Goals) {
     let date = match date_str {
         Some(d) => d,
         None => Local::now().date_naive().format("%Y-%m-%d").to_string(),
@@ -224,31 +690,69 @@ fn show_summary(logs: &[DailyLog], date_str: Option<String>) {
     if let Some(log) = logs.iter().find(|l| l.date == date) {
         println!("{} {}", "Nutrition Summary for".bold(), log.date.bold());
         println!("{}", "-------------------------".bold());
-        println!(
-            "{}: {}",
-            "Calories".green(),
-            log.calories.to_string().green().bold()
+        print_metric_progress(
+            &"Calories".green().to_string(),
+            log.calories(),
+            &log.calories().to_string().green().bold().to_string(),
+            "",
+            goals.calories,
         );
-        println!(
-            "{}: {}",
-            "Water".blue(),
-            format!("{:.1} fl oz", log.water).blue().bold()
+        print_metric_progress(
+            &"Water".blue().to_string(),
+            log.water(),
+            &format!("{:.1} fl oz", log.water())
+                .blue()
+                .bold()
+                .to_string(),
+            " fl oz",
+            goals.water,
         );
-        println!(
-            "{}: {}",
-            "Protein".yellow(),
-            format!("{:.1}g", log.protein).yellow().bold()
+        print_metric_progress(
+            &"Protein".yellow().to_string(),
+            log.protein(),
+            &format!("{:.1}g", log.protein()).yellow().bold().to_string(),
+            "g",
+            goals.protein,
         );
-        println!(
-            "{}: {}",
-            "Carbs".purple(),
-            format!("{:.1}g", log.carbs).purple().bold()
+        print_metric_progress(
+            &"Carbs".purple().to_string(),
+            log.carbs(),
+            &format!("{:.1}g", log.carbs()).purple().bold().to_string(),
+            "g",
+            goals.carbs,
         );
-        println!(
-            "{}: {}",
-            "Fat".red(),
-            format!("{:.1}g", log.fat).red().bold()
+        print_metric_progress(
+            &"Fat".red().to_string(),
+            log.fat(),
+            &format!("{:.1}g", log.fat()).red().bold().to_string(),
+            "g",
+            goals.fat,
         );
+
+        if list {
+            println!("\n{}", "Entries".bold());
+            println!("{}", "-------------------------".bold());
+            let mut entries = log.entries.clone();
+            entries.sort_by(|a, b| a.time.cmp(&b.time));
+
+            for entry in entries {
+                match entry.source {
+                    Some(source) => println!(
+                        "{}  {:?}  {}  {}",
+                        entry.time.dimmed(),
+                        entry.kind,
+                        entry.amount,
+                        format!("({})", source).dimmed()
+                    ),
+                    None => println!(
+                        "{}  {:?}  {}",
+                        entry.time.dimmed(),
+                        entry.kind,
+                        entry.amount
+                    ),
+                }
+            }
+        }
     } else {
         println!("No data found for {}", date);
     }
@@ -273,53 +777,386 @@ fn show_all_logs(logs: &[DailyLog]) {
         println!(
             "{}: {}",
             "Calories".green(),
-            log.calories.to_string().green().bold()
+            log.calories().to_string().green().bold()
         );
         println!(
             "{}: {}",
             "Water".blue(),
-            format!("{:.1} fl oz", log.water).blue().bold()
+            format!("{:.1} fl oz", log.water()).blue().bold()
         );
         println!(
             "{}: {}",
             "Protein".yellow(),
-            format!("{:.1}g", log.protein).yellow().bold()
+            format!("{:.1}g", log.protein()).yellow().bold()
         );
         println!(
             "{}: {}",
             "Carbs".purple(),
-            format!("{:.1}g", log.carbs).purple().bold()
+            format!("{:.1}g", log.carbs()).purple().bold()
         );
         println!(
             "{}: {}",
             "Fat".red(),
-            format!("{:.1}g", log.fat).red().bold()
+            format!("{:.1}g", log.fat()).red().bold()
+        );
+    }
+}
+
+fn handle_food_command(action: FoodCommands, data_dir: &std::path::Path) -> io::Result<()> {
+    match action {
+        FoodCommands::Add {
+            name,
+            kcal,
+            protein,
+            carbs,
+            fat,
+        } => {
+            let mut db = food::load_food_db(data_dir);
+            food::add_food(&mut db, &name, kcal, protein, carbs, fat);
+            food::save_food_db(data_dir, &db)?;
+            println!(
+                "{} \"{}\" {}",
+                "Added".green().bold(),
+                name,
+                "to the food database.".green()
+            );
+        }
+    }
+    Ok(())
+}
+
+fn handle_goals_command(action: GoalsCommands) -> io::Result<()> {
+    match action {
+        GoalsCommands::Set {
+            calories,
+            water,
+            protein,
+            carbs,
+            fat,
+        } => {
+            let mut goals = goals::load_goals();
+            if calories.is_some() {
+                goals.calories = calories;
+            }
+            if water.is_some() {
+                goals.water = water;
+            }
+            if protein.is_some() {
+                goals.protein = protein;
+            }
+            if carbs.is_some() {
+                goals.carbs = carbs;
+            }
+            if fat.is_some() {
+                goals.fat = fat;
+            }
+            goals::save_goals(&goals)?;
+            println!("{}", "Updated nutrition goals.".bold());
+        }
+    }
+    Ok(())
+}
+
+fn handle_recipe_command(action: RecipeCommands, data_dir: &std::path::Path) -> io::Result<()> {
+    match action {
+        RecipeCommands::Add {
+            name,
+            ingredients,
+            servings_yield,
+        } => {
+            let mut recipes = recipes::load_recipes(data_dir);
+            recipes::add_recipe(&mut recipes, &name, servings_yield, &ingredients);
+            recipes::save_recipes(data_dir, &recipes)?;
+            println!(
+                "{} \"{}\" {}",
+                "Saved recipe".green().bold(),
+                name,
+                format!(
+                    "({} ingredients, yields {} servings).",
+                    ingredients.len(),
+                    servings_yield
+                )
+                .green()
+            );
+        }
+        RecipeCommands::List => {
+            let recipes = recipes::load_recipes(data_dir);
+            if recipes.is_empty() {
+                println!("{}", "No recipes saved yet.".bold());
+            } else {
+                for recipe in recipes.values() {
+                    println!(
+                        "{} ({} ingredients, yields {} servings)",
+                        recipe.name.bold(),
+                        recipe.ingredients.len(),
+                        recipe.servings_yield
+                    );
+                }
+            }
+        }
+        RecipeCommands::Show { name } => {
+            let recipes = recipes::load_recipes(data_dir);
+            match recipes.get(&food::normalize(&name)) {
+                Some(recipe) => {
+                    println!(
+                        "{} ({} servings)",
+                        recipe.name.bold(),
+                        recipe.servings_yield
+                    );
+                    for ingredient in &recipe.ingredients {
+                        match &ingredient.unit {
+                            Some(unit) => {
+                                println!("  {} {} {}", ingredient.quantity, unit, ingredient.name)
+                            }
+                            None => println!("  {} {}", ingredient.quantity, ingredient.name),
+                        }
+                    }
+                }
+                None => println!("No recipe named \"{}\" found.", name),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// One level of an 8-step sparkline, from lowest to highest.
+const SPARK_LEVELS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Renders a single-line sparkline of `values`, scaled so the largest value maps to
+/// the tallest bar. An all-zero series renders as a flat line at the lowest level.
+fn sparkline(values: &[f32]) -> String {
+    let max = values.iter().cloned().fold(0.0_f32, f32::max);
+
+    values
+        .iter()
+        .map(|&v| {
+            let level = if max > 0.0 {
+                ((v / max) * (SPARK_LEVELS.len() - 1) as f32).round() as usize
+            } else {
+                0
+            };
+            SPARK_LEVELS[level.min(SPARK_LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+/// The last `days` dates, ending with today, oldest first.
+fn trend_dates(days: u32) -> Vec<String> {
+    let today = Local::now().date_naive();
+    (0..days)
+        .rev()
+        .map(|offset| {
+            (today - Duration::days(offset as i64))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect()
+}
+
+/// One day's value for a charted metric, used both to render the terminal chart and
+/// to serialize `--export` output.
+#[derive(Serialize)]
+struct TrendPoint {
+    date: String,
+    metric: String,
+    value: f32,
+}
+
+/// Daily average, min, and max of a (non-empty) trend series.
+fn trend_stats(series: &[f32]) -> (f32, f32, f32) {
+    let sum: f32 = series.iter().sum();
+    let avg = sum / series.len() as f32;
+    let min = series.iter().cloned().fold(f32::INFINITY, f32::min);
+    let max = series.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+    (avg, min, max)
+}
+
+/// Number of days in `series` that met or exceeded `target`.
+fn days_on_target(series: &[f32], target: f32) -> usize {
+    series.iter().filter(|&&v| v >= target).count()
+}
+
+fn print_trend_chart(
+    metric: TrendMetric,
+    dates: &[String],
+    series: &[f32],
+    days: u32,
+    goals: &Goals,
+) {
+    let (avg, min, max) = trend_stats(series);
+    let unit = metric.unit();
+
+    println!(
+        "{} {}",
+        metric.label().bold(),
+        format!(
+            "(last {} days, {} to {})",
+            days,
+            dates[0],
+            dates[dates.len() - 1]
+        )
+        .dimmed()
+    );
+    println!("{}", sparkline(series));
+    println!(
+        "  avg {:.1}{unit}  min {:.1}{unit}  max {:.1}{unit}",
+        avg, min, max
+    );
+
+    if let Some(target) = metric.goal(goals) {
+        println!(
+            "  on target: {}/{} days",
+            days_on_target(series, target),
+            series.len()
         );
     }
 }
 
+fn export_trend(
+    metrics: &[TrendMetric],
+    dates: &[String],
+    logs: &[DailyLog],
+    format: ExportFormat,
+) {
+    let points: Vec<TrendPoint> = metrics
+        .iter()
+        .flat_map(|&metric| {
+            dates.iter().map(move |date| TrendPoint {
+                date: date.clone(),
+                metric: metric.label().to_string(),
+                value: logs
+                    .iter()
+                    .find(|log| &log.date == date)
+                    .map(|log| metric.value(log))
+                    .unwrap_or(0.0),
+            })
+        })
+        .collect();
+
+    match format {
+        ExportFormat::Csv => {
+            println!("date,metric,value");
+            for point in points {
+                println!("{},{},{}", point.date, point.metric, point.value);
+            }
+        }
+        ExportFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&points)
+                    .expect("trend points always serialize to JSON")
+            );
+        }
+    }
+}
+
+fn show_trend(
+    logs: &[DailyLog],
+    days: u32,
+    metric: TrendMetric,
+    all: bool,
+    export: Option<ExportFormat>,
+    goals: &Goals,
+) {
+    // `--days` is validated to be at least 1 at the CLI layer; guard here too so this
+    // function is never handed a range that would divide by zero or index an empty
+    // `dates` vec.
+    let days = days.max(1);
+    let dates = trend_dates(days);
+    let metrics: Vec<TrendMetric> = if all {
+        TrendMetric::all().to_vec()
+    } else {
+        vec![metric]
+    };
+
+    if let Some(format) = export {
+        export_trend(&metrics, &dates, logs, format);
+        return;
+    }
+
+    for (i, metric) in metrics.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        let series: Vec<f32> = dates
+            .iter()
+            .map(|date| {
+                logs.iter()
+                    .find(|log| &log.date == date)
+                    .map(|log| metric.value(log))
+                    .unwrap_or(0.0)
+            })
+            .collect();
+        print_trend_chart(*metric, &dates, &series, days, goals);
+    }
+}
+
+fn handle_cache_command(action: CacheCommands, data_dir: &std::path::Path) -> io::Result<()> {
+    match action {
+        CacheCommands::Clear => {
+            remote::clear_cache(data_dir)?;
+            println!("{}", "Cleared the nutrition lookup cache.".bold());
+        }
+    }
+    Ok(())
+}
+
 fn main() -> io::Result<()> {
     let cli = Cli::parse();
 
-    let mut logs = load_logs()?;
+    let data_dir = storage::resolve_data_dir(cli.data_dir.clone());
+    storage::migrate_legacy_data_file(&data_dir);
+    let store = storage::JsonStorage::new(&data_dir);
+
+    let mut logs = store.load_logs()?;
 
     match &cli.command {
         Some(Commands::Log { nutrition_type }) => {
-            log_nutrition(&mut logs, nutrition_type.clone());
+            log_nutrition(&mut logs, nutrition_type.clone(), &data_dir);
         }
-        Some(Commands::Summary { date }) => {
-            show_summary(&logs, date.clone());
+        Some(Commands::Summary { date, list }) => {
+            show_summary(&logs, date.clone(), *list, &goals::load_goals());
         }
         Some(Commands::History) => {
             show_all_logs(&logs);
         }
         Some(Commands::Reset) => {
-            reset_today_log(&mut logs)?;
+            reset_today_log(&mut logs);
+        }
+        Some(Commands::Undo) => {
+            undo_last_entry(&mut logs);
+        }
+        Some(Commands::Food { action }) => {
+            handle_food_command(action.clone(), &data_dir)?;
+        }
+        Some(Commands::Goals { action }) => {
+            handle_goals_command(action.clone())?;
+        }
+        Some(Commands::Recipe { action }) => {
+            handle_recipe_command(action.clone(), &data_dir)?;
+        }
+        Some(Commands::Cache { action }) => {
+            handle_cache_command(action.clone(), &data_dir)?;
+        }
+        Some(Commands::Trend {
+            days,
+            metric,
+            all,
+            export,
+        }) => {
+            show_trend(&logs, *days, *metric, *all, *export, &goals::load_goals());
         }
         None => {
             // If calories are provided directly, log them
             if let Some(calories) = cli.calories {
-                log_nutrition(&mut logs, NutritionType::Calories { amount: calories });
+                log_nutrition(
+                    &mut logs,
+                    NutritionType::Calories {
+                        amount: calories,
+                        at: None,
+                    },
+                    &data_dir,
+                );
             } else {
                 // No command or calories provided, print help
                 Cli::command().print_help().unwrap();
@@ -328,6 +1165,54 @@ fn main() -> io::Result<()> {
         }
     }
 
-    save_logs(&logs)?;
+    store.save_logs(&logs)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod trend_tests {
+    use super::*;
+
+    #[test]
+    fn trend_dates_spans_the_requested_window_ending_today() {
+        let dates = trend_dates(5);
+        assert_eq!(dates.len(), 5);
+        assert_eq!(
+            dates.last().unwrap(),
+            &Local::now().date_naive().format("%Y-%m-%d").to_string()
+        );
+        // Oldest first.
+        assert!(dates.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn trend_dates_handles_a_single_day() {
+        assert_eq!(trend_dates(1).len(), 1);
+    }
+
+    #[test]
+    fn sparkline_is_flat_for_an_all_zero_series() {
+        assert_eq!(sparkline(&[0.0, 0.0, 0.0]), "▁▁▁");
+    }
+
+    #[test]
+    fn sparkline_scales_to_the_max_value() {
+        let chart = sparkline(&[0.0, 50.0, 100.0]);
+        let levels: Vec<char> = chart.chars().collect();
+        assert_eq!(levels[0], SPARK_LEVELS[0]);
+        assert_eq!(levels[2], SPARK_LEVELS[SPARK_LEVELS.len() - 1]);
+    }
+
+    #[test]
+    fn trend_stats_computes_avg_min_max() {
+        let (avg, min, max) = trend_stats(&[10.0, 20.0, 30.0]);
+        assert!((avg - 20.0).abs() < 1e-6);
+        assert_eq!(min, 10.0);
+        assert_eq!(max, 30.0);
+    }
+
+    #[test]
+    fn days_on_target_counts_days_meeting_the_goal() {
+        assert_eq!(days_on_target(&[50.0, 100.0, 150.0], 100.0), 2);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::food::{self, FoodDatabase, IngredientAmount, Macros};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A reusable meal: a yield (how many servings the ingredient list makes) and the
+/// ingredients that make it up, so logging it scales every macro by the servings eaten.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Recipe {
+    pub name: String,
+    pub servings_yield: f32,
+    pub ingredients: Vec<IngredientAmount>,
+}
+
+pub type RecipeBook = BTreeMap<String, Recipe>;
+
+fn recipes_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("recipes.json")
+}
+
+pub fn load_recipes(data_dir: &Path) -> RecipeBook {
+    fs::read_to_string(recipes_path(data_dir))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_recipes(data_dir: &Path, recipes: &RecipeBook) -> std::io::Result<()> {
+    let json = serde_json::to_string_pretty(recipes)?;
+    fs::write(recipes_path(data_dir), json)
+}
+
+/// Adds or overwrites a recipe, parsing each ingredient line the same way
+/// `cali log food` does.
+pub fn add_recipe(
+    recipes: &mut RecipeBook,
+    name: &str,
+    servings_yield: f32,
+    ingredient_lines: &[String],
+) {
+    let ingredients = ingredient_lines
+        .iter()
+        .filter_map(|line| food::parse_ingredient_line(line))
+        .collect();
+
+    recipes.insert(
+        food::normalize(name),
+        Recipe {
+            name: name.to_string(),
+            servings_yield,
+            ingredients,
+        },
+    );
+}
+
+/// Resolves a recipe's macros for `servings` servings, scaling down from its full
+/// yield. Returns the names of any ingredients that didn't match the food database.
+pub fn resolve_recipe(recipe: &Recipe, servings: f32, db: &FoodDatabase) -> (Macros, Vec<String>) {
+    let mut totals = Macros::default();
+    let mut unmatched = Vec::new();
+
+    for ingredient in &recipe.ingredients {
+        match food::resolve_ingredient(ingredient, db) {
+            Some(macros) => totals.add(macros),
+            None => unmatched.push(ingredient.name.clone()),
+        }
+    }
+
+    let scale = if recipe.servings_yield > 0.0 {
+        servings / recipe.servings_yield
+    } else {
+        0.0
+    };
+
+    (totals.scale(scale), unmatched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rice_db() -> FoodDatabase {
+        serde_json::from_str(
+            r#"{"rice": {"kcal": 130.0, "protein": 2.7, "carbs": 28.0, "fat": 0.3}}"#,
+        )
+        .unwrap()
+    }
+
+    fn rice_recipe(servings_yield: f32) -> Recipe {
+        Recipe {
+            name: "rice bowl".to_string(),
+            servings_yield,
+            ingredients: vec![IngredientAmount {
+                quantity: 100.0,
+                unit: None,
+                name: "rice".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn resolve_recipe_scales_macros_by_servings_eaten_over_yield() {
+        let db = rice_db();
+        let recipe = rice_recipe(4.0);
+
+        let (macros, unmatched) = resolve_recipe(&recipe, 2.0, &db);
+
+        assert!(unmatched.is_empty());
+        assert_eq!(macros.kcal, 65.0);
+        assert_eq!(macros.protein, 1.35);
+        assert_eq!(macros.carbs, 14.0);
+        assert_eq!(macros.fat, 0.15);
+    }
+
+    #[test]
+    fn resolve_recipe_zeroes_out_when_servings_yield_is_not_positive() {
+        let db = rice_db();
+        let recipe = rice_recipe(0.0);
+
+        let (macros, unmatched) = resolve_recipe(&recipe, 2.0, &db);
+
+        assert!(unmatched.is_empty());
+        assert_eq!(macros.kcal, 0.0);
+        assert_eq!(macros.protein, 0.0);
+        assert_eq!(macros.carbs, 0.0);
+        assert_eq!(macros.fat, 0.0);
+    }
+
+    #[test]
+    fn resolve_recipe_reports_unmatched_ingredients() {
+        let db = rice_db();
+        let mut recipe = rice_recipe(4.0);
+        recipe.ingredients.push(IngredientAmount {
+            quantity: 1.0,
+            unit: None,
+            name: "unobtainium".to_string(),
+        });
+
+        let (_, unmatched) = resolve_recipe(&recipe, 4.0, &db);
+
+        assert_eq!(unmatched, vec!["unobtainium".to_string()]);
+    }
+}
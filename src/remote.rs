@@ -0,0 +1,193 @@
+use crate::food::FoodEntry;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Env var pointing at a nutrition lookup API, queried as `{url}?q={ingredient}` and
+/// expected to respond with a JSON body matching [`FoodEntry`]. Unset by default,
+/// in which case online lookups are simply unavailable.
+pub const API_URL_ENV: &str = "CALI_NUTRITION_API_URL";
+
+fn cache_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("nutrition_cache.json")
+}
+
+/// The outcome of a single fetch attempt: either nothing was found, or a value was.
+/// Caching a `None` (rather than treating a miss the same as never having tried) is
+/// what lets `Cache::fetch_or` skip retrying a known-missing query until it expires.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum Fetchable<T> {
+    None,
+    Fetched(T),
+}
+
+/// One cached fetch attempt, with the time it happened so it can expire.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheRecord<T> {
+    value: Fetchable<T>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// An on-disk, TTL-expiring cache of fetch results, keyed by query string.
+#[derive(Serialize, Deserialize, Debug)]
+struct Cache<T> {
+    entries: BTreeMap<String, CacheRecord<T>>,
+}
+
+impl<T> Default for Cache<T> {
+    fn default() -> Self {
+        Cache {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Clone + Serialize + for<'de> Deserialize<'de>> Cache<T> {
+    fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Returns the cached value for `key` if it was fetched within `ttl` of now,
+    /// otherwise calls `fetch`, caches whatever it returns (including `None`, so a
+    /// miss doesn't trigger another network call until the cache expires), and
+    /// returns that instead.
+    fn fetch_or(
+        &mut self,
+        key: &str,
+        ttl: Duration,
+        fetch: impl FnOnce() -> Option<T>,
+    ) -> Option<T> {
+        let fresh = self
+            .entries
+            .get(key)
+            .is_some_and(|record| Utc::now() - record.fetched_at < ttl);
+
+        if !fresh {
+            let value = match fetch() {
+                Some(v) => Fetchable::Fetched(v),
+                None => Fetchable::None,
+            };
+            self.entries.insert(
+                key.to_string(),
+                CacheRecord {
+                    value,
+                    fetched_at: Utc::now(),
+                },
+            );
+        }
+
+        match self.entries.get(key).map(|record| &record.value) {
+            Some(Fetchable::Fetched(value)) => Some(value.clone()),
+            Some(Fetchable::None) | None => None,
+        }
+    }
+}
+
+/// Deletes the on-disk nutrition lookup cache, if any.
+pub fn clear_cache(data_dir: &Path) -> io::Result<()> {
+    let path = cache_path(data_dir);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Looks up `name` against the configured nutrition API, going through the on-disk
+/// cache first. A cache hit younger than `ttl` is returned without a network call.
+/// Returns `None` if the API isn't configured or doesn't recognize the ingredient.
+pub fn lookup_online(data_dir: &Path, name: &str, ttl: Duration) -> Option<FoodEntry> {
+    let path = cache_path(data_dir);
+    let mut cache: Cache<FoodEntry> = Cache::load(&path);
+
+    let result = cache.fetch_or(name, ttl, || fetch_food_entry(name));
+    let _ = cache.save(&path);
+
+    result
+}
+
+/// Queries the nutrition API configured via [`API_URL_ENV`] for `name`.
+fn fetch_food_entry(name: &str) -> Option<FoodEntry> {
+    let base_url = std::env::var(API_URL_ENV).ok()?;
+    let url = format!("{base_url}?q={}", urlencoding::encode(name));
+
+    ureq::get(&url).call().ok()?.into_json::<FoodEntry>().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn fetch_or_caches_a_fresh_value_and_skips_refetching() {
+        let mut cache: Cache<i32> = Cache::default();
+        let calls = Cell::new(0);
+
+        let first = cache.fetch_or("key", Duration::minutes(5), || {
+            calls.set(calls.get() + 1);
+            Some(42)
+        });
+        let second = cache.fetch_or("key", Duration::minutes(5), || {
+            calls.set(calls.get() + 1);
+            Some(99)
+        });
+
+        assert_eq!(first, Some(42));
+        assert_eq!(second, Some(42));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn fetch_or_refetches_once_the_ttl_has_expired() {
+        let mut cache: Cache<i32> = Cache::default();
+        cache.entries.insert(
+            "key".to_string(),
+            CacheRecord {
+                value: Fetchable::Fetched(1),
+                fetched_at: Utc::now() - Duration::minutes(10),
+            },
+        );
+
+        let result = cache.fetch_or("key", Duration::minutes(5), || Some(2));
+
+        assert_eq!(result, Some(2));
+        assert!(matches!(
+            cache.entries.get("key").unwrap().value,
+            Fetchable::Fetched(2)
+        ));
+    }
+
+    #[test]
+    fn fetch_or_negatively_caches_a_miss() {
+        let mut cache: Cache<i32> = Cache::default();
+        let calls = Cell::new(0);
+
+        let first = cache.fetch_or("key", Duration::minutes(5), || {
+            calls.set(calls.get() + 1);
+            None
+        });
+        let second = cache.fetch_or("key", Duration::minutes(5), || {
+            calls.set(calls.get() + 1);
+            Some(7)
+        });
+
+        assert_eq!(first, None);
+        assert_eq!(second, None);
+        assert_eq!(calls.get(), 1);
+        assert!(matches!(
+            cache.entries.get("key").unwrap().value,
+            Fetchable::None
+        ));
+    }
+}
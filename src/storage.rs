@@ -0,0 +1,279 @@
+use crate::{DailyLog, NutritionKind};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Persists the logged [`DailyLog`] history. A `JsonStorage` backs this today; a
+/// future SQLite backend can implement the same trait without touching call sites.
+pub trait Storage {
+    fn load_logs(&self) -> io::Result<Vec<DailyLog>>;
+    fn save_logs(&self, logs: &[DailyLog]) -> io::Result<()>;
+}
+
+pub struct JsonStorage {
+    path: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(data_dir: &Path) -> Self {
+        let mut path = data_dir.to_path_buf();
+        path.push("cali_data.json");
+        JsonStorage { path }
+    }
+}
+
+/// The pre-entry-log on-disk schema: one flat summed total per metric, per day.
+/// Kept only so `load_logs` can migrate an old data file instead of discarding it.
+#[derive(Deserialize)]
+struct LegacyDailyLog {
+    date: String,
+    calories: f32,
+    water: f32,
+    protein: f32,
+    carbs: f32,
+    fat: f32,
+}
+
+fn migrate_legacy_log(legacy: LegacyDailyLog) -> DailyLog {
+    let mut log = DailyLog::new(legacy.date);
+
+    for (kind, amount) in [
+        (NutritionKind::Calories, legacy.calories),
+        (NutritionKind::Water, legacy.water),
+        (NutritionKind::Protein, legacy.protein),
+        (NutritionKind::Carbs, legacy.carbs),
+        (NutritionKind::Fat, legacy.fat),
+    ] {
+        if amount != 0.0 {
+            log.push_entry(kind, amount, "00:00".to_string(), None);
+        }
+    }
+
+    log
+}
+
+/// The per-entry on-disk schema used before entries were tagged with an undo `group`.
+/// Kept only so `load_logs` can migrate an old data file instead of discarding it.
+#[derive(Deserialize)]
+struct DailyLogV1 {
+    date: String,
+    entries: Vec<EntryV1>,
+}
+
+#[derive(Deserialize)]
+struct EntryV1 {
+    time: String,
+    kind: NutritionKind,
+    amount: f32,
+    source: Option<String>,
+}
+
+/// Migrates a pre-`group` log by giving each entry its own standalone group, so
+/// `undo` removes the migrated entries one at a time. This never merges unrelated
+/// old entries into a single group.
+fn migrate_v1_log(v1: DailyLogV1) -> DailyLog {
+    let mut log = DailyLog::new(v1.date);
+
+    for entry in v1.entries {
+        log.push_entry(entry.kind, entry.amount, entry.time, entry.source);
+    }
+
+    log
+}
+
+impl Storage for JsonStorage {
+    fn load_logs(&self) -> io::Result<Vec<DailyLog>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let contents = fs::read_to_string(&self.path)?;
+
+        if let Ok(logs) = serde_json::from_str::<Vec<DailyLog>>(&contents) {
+            return Ok(logs);
+        }
+
+        if let Ok(v1_logs) = serde_json::from_str::<Vec<DailyLogV1>>(&contents) {
+            eprintln!(
+                "Migrating {} to tag existing entries with an undo group...",
+                self.path.display()
+            );
+            let migrated: Vec<DailyLog> = v1_logs.into_iter().map(migrate_v1_log).collect();
+            self.save_logs(&migrated)?;
+            return Ok(migrated);
+        }
+
+        if let Ok(legacy_logs) = serde_json::from_str::<Vec<LegacyDailyLog>>(&contents) {
+            eprintln!(
+                "Migrating {} from the old flat-totals format to the per-entry format...",
+                self.path.display()
+            );
+            let migrated: Vec<DailyLog> = legacy_logs.into_iter().map(migrate_legacy_log).collect();
+            self.save_logs(&migrated)?;
+            return Ok(migrated);
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{} doesn't match a recognized schema. Refusing to overwrite it - move it \
+                 aside (or delete it) and re-run if you want to start fresh.",
+                self.path.display()
+            ),
+        ))
+    }
+
+    fn save_logs(&self, logs: &[DailyLog]) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(logs)?;
+        fs::write(&self.path, json)
+    }
+}
+
+/// The in-tree location this crate used to hard-code its data file to, via
+/// `CARGO_MANIFEST_DIR`. Kept only so `migrate_legacy_data_file` can find and move
+/// any data a previously-built binary left behind.
+fn legacy_data_file() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("data")
+        .join("cali_data.json")
+}
+
+/// Resolves the directory `cali` should store its data file in, in order of
+/// precedence: the `--data-dir` flag, the `CALI_DATA_DIR` env var, then the
+/// platform's standard data directory (e.g. `~/.local/share/cali` on Linux).
+pub fn resolve_data_dir(data_dir_flag: Option<PathBuf>) -> PathBuf {
+    let dir = data_dir_flag
+        .or_else(|| std::env::var_os("CALI_DATA_DIR").map(PathBuf::from))
+        .unwrap_or_else(|| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("cali")
+        });
+
+    fs::create_dir_all(&dir).expect("Failed to create data directory");
+    dir
+}
+
+/// One-time migration: if the old in-tree data file exists and nothing has been
+/// written to the new location yet, move it over.
+pub fn migrate_legacy_data_file(data_dir: &Path) {
+    let legacy = legacy_data_file();
+    let new_path = data_dir.join("cali_data.json");
+
+    if legacy.exists() && !new_path.exists() && fs::rename(&legacy, &new_path).is_ok() {
+        eprintln!(
+            "Moved existing data file from {} to {}",
+            legacy.display(),
+            new_path.display()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh, unique scratch directory under the system temp dir, so tests can
+    /// exercise `JsonStorage`'s actual file I/O without colliding with each other.
+    fn scratch_dir(label: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!(
+            "cali_storage_test_{label}_{}_{n}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrate_legacy_log_skips_zero_metrics_and_stamps_midnight() {
+        let legacy = LegacyDailyLog {
+            date: "2026-01-01".to_string(),
+            calories: 2000.0,
+            water: 0.0,
+            protein: 100.0,
+            carbs: 0.0,
+            fat: 0.0,
+        };
+
+        let log = migrate_legacy_log(legacy);
+
+        assert_eq!(log.date, "2026-01-01");
+        assert_eq!(log.entries.len(), 2);
+        assert!(log.entries.iter().all(|e| e.time == "00:00"));
+        assert!(log
+            .entries
+            .iter()
+            .any(|e| matches!(e.kind, NutritionKind::Calories) && e.amount == 2000.0));
+        assert!(log
+            .entries
+            .iter()
+            .any(|e| matches!(e.kind, NutritionKind::Protein) && e.amount == 100.0));
+    }
+
+    #[test]
+    fn migrate_v1_log_gives_each_old_entry_its_own_group() {
+        let v1 = DailyLogV1 {
+            date: "2026-01-01".to_string(),
+            entries: vec![
+                EntryV1 {
+                    time: "09:00".to_string(),
+                    kind: NutritionKind::Calories,
+                    amount: 89.0,
+                    source: Some("banana".to_string()),
+                },
+                EntryV1 {
+                    time: "09:00".to_string(),
+                    kind: NutritionKind::Protein,
+                    amount: 1.1,
+                    source: Some("banana".to_string()),
+                },
+            ],
+        };
+
+        let log = migrate_v1_log(v1);
+
+        assert_eq!(log.entries[0].group, 0);
+        assert_eq!(log.entries[1].group, 1);
+    }
+
+    #[test]
+    fn load_logs_migrates_a_legacy_flat_totals_file() {
+        let dir = scratch_dir("legacy");
+        let storage = JsonStorage::new(&dir);
+        fs::write(
+            dir.join("cali_data.json"),
+            r#"[{"date": "2026-01-01", "calories": 2000.0, "water": 0.0, "protein": 100.0, "carbs": 0.0, "fat": 0.0}]"#,
+        )
+        .unwrap();
+
+        let logs = storage.load_logs().unwrap();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].entries.len(), 2);
+
+        // The migration should also have rewritten the file in the current schema.
+        let reloaded = storage.load_logs().unwrap();
+        assert_eq!(reloaded.len(), 1);
+        assert_eq!(reloaded[0].entries.len(), 2);
+    }
+
+    #[test]
+    fn load_logs_rejects_an_unrecognized_schema_without_overwriting_it() {
+        let dir = scratch_dir("bogus");
+        let storage = JsonStorage::new(&dir);
+        let original = r#"{"this": "is not a recognized schema"}"#;
+        fs::write(dir.join("cali_data.json"), original).unwrap();
+
+        let result = storage.load_logs();
+
+        assert!(result.is_err());
+        assert_eq!(
+            fs::read_to_string(dir.join("cali_data.json")).unwrap(),
+            original
+        );
+    }
+}